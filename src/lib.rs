@@ -1,9 +1,32 @@
+mod stream;
+mod zran;
+
 use jni::JNIEnv;
 use jni::objects::JClass;
 use jni::sys::{JNI_FALSE, JNI_TRUE, jboolean, jint, jlong};
-use libdeflater::{CompressionLvl, Compressor, Decompressor};
+use libdeflater::{Adler32, CompressionLvl, Compressor, Crc, Decompressor};
 use std::ffi::c_int;
 use std::ptr;
+use stream::{StreamDeflateContext, StreamInflateContext};
+use zran::ZranIndex;
+
+#[derive(Clone, Copy)]
+enum DeflateFormat {
+    Zlib,
+    Gzip,
+    Raw,
+}
+
+impl DeflateFormat {
+    fn from_c_int(value: c_int) -> Option<Self> {
+        match value {
+            0 => Some(DeflateFormat::Zlib),
+            1 => Some(DeflateFormat::Gzip),
+            2 => Some(DeflateFormat::Raw),
+            _ => None,
+        }
+    }
+}
 
 enum DeflateResult {
     Success(usize),
@@ -12,7 +35,7 @@ enum DeflateResult {
 }
 
 enum InflateResult {
-    Success,
+    Success(usize),
     InsufficientSpace,
     BadData,
     Error,
@@ -20,17 +43,19 @@ enum InflateResult {
 
 struct DeflateContext {
     compressor: Compressor,
+    format: DeflateFormat,
 }
 
 struct InflateContext {
     decompressor: Decompressor,
+    format: DeflateFormat,
 }
 
-fn deflate_init(level: c_int) -> Option<*mut DeflateContext> {
+fn deflate_init(level: c_int, format: DeflateFormat) -> Option<*mut DeflateContext> {
     match CompressionLvl::new(level) {
         Ok(lvl) => {
             let compressor = Compressor::new(lvl);
-            let context = Box::new(DeflateContext { compressor });
+            let context = Box::new(DeflateContext { compressor, format });
             Some(Box::into_raw(context))
         }
         Err(_) => None,
@@ -52,12 +77,24 @@ unsafe fn deflate_process(
     let source_slice = std::slice::from_raw_parts(source_ptr, source_len);
     let dest_slice = std::slice::from_raw_parts_mut(dest_ptr, dest_len);
 
-    match context.compressor.zlib_compress(source_slice, dest_slice) {
+    let result = match context.format {
+        DeflateFormat::Zlib => context.compressor.zlib_compress(source_slice, dest_slice),
+        DeflateFormat::Gzip => context.compressor.gzip_compress(source_slice, dest_slice),
+        DeflateFormat::Raw => context.compressor.deflate_compress(source_slice, dest_slice),
+    };
+
+    match result {
         Ok(sz) => DeflateResult::Success(sz),
         Err(_) => DeflateResult::InsufficientSpace,
     }
 }
 
+fn inflate_init(format: DeflateFormat) -> *mut InflateContext {
+    let decompressor = Decompressor::new();
+    let context = Box::new(InflateContext { decompressor, format });
+    Box::into_raw(context)
+}
+
 unsafe fn inflate_process(
     ctx: *mut InflateContext,
     source_ptr: *const u8,
@@ -73,24 +110,40 @@ unsafe fn inflate_process(
     let source_slice = std::slice::from_raw_parts(source_ptr, source_len);
     let dest_slice = std::slice::from_raw_parts_mut(dest_ptr, dest_len);
 
-    match context
-        .decompressor
-        .zlib_decompress(source_slice, dest_slice)
-    {
-        Ok(_) => InflateResult::Success,
+    let result = match context.format {
+        DeflateFormat::Zlib => context.decompressor.zlib_decompress(source_slice, dest_slice),
+        DeflateFormat::Gzip => context.decompressor.gzip_decompress(source_slice, dest_slice),
+        DeflateFormat::Raw => context.decompressor.deflate_decompress(source_slice, dest_slice),
+    };
+
+    match result {
+        Ok(sz) => InflateResult::Success(sz),
         Err(libdeflater::DecompressionError::InsufficientSpace) => InflateResult::InsufficientSpace,
         Err(libdeflater::DecompressionError::BadData) => InflateResult::BadData,
         Err(_) => InflateResult::Error,
     }
 }
 
+unsafe fn deflate_bound(ctx: *mut DeflateContext, input_len: usize) -> Option<usize> {
+    if ctx.is_null() {
+        return None;
+    }
+    let context = &mut *ctx;
+
+    Some(match context.format {
+        DeflateFormat::Zlib => context.compressor.zlib_compress_bound(input_len),
+        DeflateFormat::Gzip => context.compressor.gzip_compress_bound(input_len),
+        DeflateFormat::Raw => context.compressor.deflate_compress_bound(input_len),
+    })
+}
+
 #[unsafe(no_mangle)]
 pub unsafe extern "system" fn Java_com_velocitypowered_natives_compression_NativeZlibDeflate_init(
     mut env: JNIEnv,
     _class: JClass,
     level: jint,
 ) -> jlong {
-    match deflate_init(level) {
+    match deflate_init(level, DeflateFormat::Zlib) {
         Some(ctx) => ctx as jlong,
         None => {
             let exception_class = env
@@ -139,14 +192,25 @@ pub unsafe extern "system" fn Java_com_velocitypowered_natives_compression_Nativ
     }
 }
 
+#[unsafe(no_mangle)]
+pub unsafe extern "system" fn Java_com_velocitypowered_natives_compression_NativeZlibDeflate_bound(
+    _env: JNIEnv,
+    _class: JClass,
+    ctx: jlong,
+    input_length: jint,
+) -> jint {
+    match deflate_bound(ctx as *mut DeflateContext, input_length as usize) {
+        Some(bound) => bound as jint,
+        None => -1,
+    }
+}
+
 #[unsafe(no_mangle)]
 pub unsafe extern "system" fn Java_com_velocitypowered_natives_compression_NativeZlibInflate_init(
     mut env: JNIEnv,
     _class: JClass,
 ) -> jlong {
-    let decompressor = Decompressor::new();
-    let context = Box::new(InflateContext { decompressor });
-    Box::into_raw(context) as jlong
+    inflate_init(DeflateFormat::Zlib) as jlong
 }
 
 #[unsafe(no_mangle)]
@@ -179,7 +243,7 @@ pub unsafe extern "system" fn Java_com_velocitypowered_natives_compression_Nativ
     );
 
     match res {
-        InflateResult::Success => JNI_TRUE,
+        InflateResult::Success(_) => JNI_TRUE,
         InflateResult::BadData => {
             let exception_class = env.find_class("java/util/zip/DataFormatException").unwrap();
             env.throw_new(exception_class, "inflate data is bad")
@@ -202,72 +266,787 @@ pub unsafe extern "system" fn Java_com_velocitypowered_natives_compression_Nativ
 }
 
 #[unsafe(no_mangle)]
-pub unsafe extern "C" fn rxz_deflate_init(level: c_int) -> *mut DeflateContext {
-    deflate_init(level).unwrap_or_else(|| ptr::null_mut())
+pub unsafe extern "system" fn Java_com_velocitypowered_natives_compression_NativeZlibInflate_processAndGetLength(
+    mut env: JNIEnv,
+    _class: JClass,
+    ctx: jlong,
+    source_address: jlong,
+    source_length: jint,
+    destination_address: jlong,
+    destination_length: jint,
+) -> jint {
+    let res = inflate_process(
+        ctx as *mut InflateContext,
+        source_address as *const u8,
+        source_length as usize,
+        destination_address as *mut u8,
+        destination_length as usize,
+    );
+
+    match res {
+        InflateResult::Success(size) => size as jint,
+        InflateResult::BadData => {
+            let exception_class = env.find_class("java/util/zip/DataFormatException").unwrap();
+            env.throw_new(exception_class, "inflate data is bad")
+                .unwrap();
+            -1
+        }
+        InflateResult::InsufficientSpace => {
+            let exception_class = env.find_class("java/util/zip/DataFormatException").unwrap();
+            env.throw_new(exception_class, "uncompressed size is inaccurate")
+                .unwrap();
+            -1
+        }
+        InflateResult::Error => {
+            let exception_class = env.find_class("java/util/zip/DataFormatException").unwrap();
+            env.throw_new(exception_class, "unknown libdeflate return code")
+                .unwrap();
+            -1
+        }
+    }
 }
 
 #[unsafe(no_mangle)]
-pub unsafe extern "C" fn rxz_deflate_free(ctx: *mut DeflateContext) {
-    if !ctx.is_null() {
-        let _ = Box::from_raw(ctx);
+pub unsafe extern "system" fn Java_com_velocitypowered_natives_compression_NativeGzipDeflate_init(
+    mut env: JNIEnv,
+    _class: JClass,
+    level: jint,
+) -> jlong {
+    match deflate_init(level, DeflateFormat::Gzip) {
+        Some(ctx) => ctx as jlong,
+        None => {
+            let exception_class = env
+                .find_class("java/lang/OutOfMemoryError")
+                .unwrap();
+            env.throw_new(exception_class, "libdeflate allocate compressor")
+                .unwrap();
+            0
+        }
     }
 }
 
 #[unsafe(no_mangle)]
-pub unsafe extern "C" fn rxz_deflate_process(
-    ctx: *mut DeflateContext,
-    source: *const u8,
-    source_length: c_int,
-    destination: *mut u8,
-    destination_length: c_int,
-) -> c_int {
+pub unsafe extern "system" fn Java_com_velocitypowered_natives_compression_NativeGzipDeflate_free(
+    _env: JNIEnv,
+    _class: JClass,
+    ctx: jlong,
+) {
+    if ctx != 0 {
+        let _ = Box::from_raw(ctx as *mut DeflateContext);
+    }
+}
+
+#[unsafe(no_mangle)]
+pub unsafe extern "system" fn Java_com_velocitypowered_natives_compression_NativeGzipDeflate_process(
+    mut env: JNIEnv,
+    _class: JClass,
+    ctx: jlong,
+    source_address: jlong,
+    source_length: jint,
+    destination_address: jlong,
+    destination_length: jint,
+) -> jint {
     let res = deflate_process(
-        ctx,
-        source,
+        ctx as *mut DeflateContext,
+        source_address as *const u8,
         source_length as usize,
-        destination,
+        destination_address as *mut u8,
         destination_length as usize,
     );
+
     match res {
-        DeflateResult::Success(sz) => sz as c_int,
+        DeflateResult::Success(size) => size as jint,
         DeflateResult::InsufficientSpace => 0,
-        DeflateResult::Error => -1,
+        DeflateResult::Error => 0,
     }
 }
 
 #[unsafe(no_mangle)]
-pub unsafe extern "C" fn rxz_inflate_init() -> *mut InflateContext {
-    let decompressor = Decompressor::new();
-    let context = Box::new(InflateContext { decompressor });
-    Box::into_raw(context)
+pub unsafe extern "system" fn Java_com_velocitypowered_natives_compression_NativeGzipInflate_init(
+    mut env: JNIEnv,
+    _class: JClass,
+) -> jlong {
+    inflate_init(DeflateFormat::Gzip) as jlong
 }
 
 #[unsafe(no_mangle)]
-pub unsafe extern "C" fn rxz_inflate_free(ctx: *mut InflateContext) {
-    if !ctx.is_null() {
-        let _ = Box::from_raw(ctx);
+pub unsafe extern "system" fn Java_com_velocitypowered_natives_compression_NativeGzipInflate_free(
+    _env: JNIEnv,
+    _class: JClass,
+    ctx: jlong,
+) {
+    if ctx != 0 {
+        let _ = Box::from_raw(ctx as *mut InflateContext);
     }
 }
 
 #[unsafe(no_mangle)]
-pub unsafe extern "C" fn rxz_inflate_process(
-    ctx: *mut InflateContext,
-    source: *const u8,
-    source_length: c_int,
-    destination: *mut u8,
-    destination_length: c_int,
-) -> c_int {
+pub unsafe extern "system" fn Java_com_velocitypowered_natives_compression_NativeGzipInflate_process(
+    mut env: JNIEnv,
+    _class: JClass,
+    ctx: jlong,
+    source_address: jlong,
+    source_length: jint,
+    destination_address: jlong,
+    destination_length: jint,
+) -> jboolean {
     let res = inflate_process(
-        ctx,
-        source,
+        ctx as *mut InflateContext,
+        source_address as *const u8,
         source_length as usize,
-        destination,
+        destination_address as *mut u8,
+        destination_length as usize,
+    );
+
+    match res {
+        InflateResult::Success(_) => JNI_TRUE,
+        InflateResult::BadData => {
+            let exception_class = env.find_class("java/util/zip/DataFormatException").unwrap();
+            env.throw_new(exception_class, "inflate data is bad")
+                .unwrap();
+            JNI_FALSE
+        }
+        InflateResult::InsufficientSpace => {
+            let exception_class = env.find_class("java/util/zip/DataFormatException").unwrap();
+            env.throw_new(exception_class, "uncompressed size is inaccurate")
+                .unwrap();
+            JNI_FALSE
+        }
+        InflateResult::Error => {
+            let exception_class = env.find_class("java/util/zip/DataFormatException").unwrap();
+            env.throw_new(exception_class, "unknown libdeflate return code")
+                .unwrap();
+            JNI_FALSE
+        }
+    }
+}
+
+#[unsafe(no_mangle)]
+pub unsafe extern "system" fn Java_com_velocitypowered_natives_compression_NativeGzipInflate_processAndGetLength(
+    mut env: JNIEnv,
+    _class: JClass,
+    ctx: jlong,
+    source_address: jlong,
+    source_length: jint,
+    destination_address: jlong,
+    destination_length: jint,
+) -> jint {
+    let res = inflate_process(
+        ctx as *mut InflateContext,
+        source_address as *const u8,
+        source_length as usize,
+        destination_address as *mut u8,
+        destination_length as usize,
+    );
+
+    match res {
+        InflateResult::Success(size) => size as jint,
+        InflateResult::BadData => {
+            let exception_class = env.find_class("java/util/zip/DataFormatException").unwrap();
+            env.throw_new(exception_class, "inflate data is bad")
+                .unwrap();
+            -1
+        }
+        InflateResult::InsufficientSpace => {
+            let exception_class = env.find_class("java/util/zip/DataFormatException").unwrap();
+            env.throw_new(exception_class, "uncompressed size is inaccurate")
+                .unwrap();
+            -1
+        }
+        InflateResult::Error => {
+            let exception_class = env.find_class("java/util/zip/DataFormatException").unwrap();
+            env.throw_new(exception_class, "unknown libdeflate return code")
+                .unwrap();
+            -1
+        }
+    }
+}
+
+#[unsafe(no_mangle)]
+pub unsafe extern "system" fn Java_com_velocitypowered_natives_compression_NativeRawDeflate_init(
+    mut env: JNIEnv,
+    _class: JClass,
+    level: jint,
+) -> jlong {
+    match deflate_init(level, DeflateFormat::Raw) {
+        Some(ctx) => ctx as jlong,
+        None => {
+            let exception_class = env
+                .find_class("java/lang/OutOfMemoryError")
+                .unwrap();
+            env.throw_new(exception_class, "libdeflate allocate compressor")
+                .unwrap();
+            0
+        }
+    }
+}
+
+#[unsafe(no_mangle)]
+pub unsafe extern "system" fn Java_com_velocitypowered_natives_compression_NativeRawDeflate_free(
+    _env: JNIEnv,
+    _class: JClass,
+    ctx: jlong,
+) {
+    if ctx != 0 {
+        let _ = Box::from_raw(ctx as *mut DeflateContext);
+    }
+}
+
+#[unsafe(no_mangle)]
+pub unsafe extern "system" fn Java_com_velocitypowered_natives_compression_NativeRawDeflate_process(
+    mut env: JNIEnv,
+    _class: JClass,
+    ctx: jlong,
+    source_address: jlong,
+    source_length: jint,
+    destination_address: jlong,
+    destination_length: jint,
+) -> jint {
+    let res = deflate_process(
+        ctx as *mut DeflateContext,
+        source_address as *const u8,
+        source_length as usize,
+        destination_address as *mut u8,
         destination_length as usize,
     );
+
     match res {
-        InflateResult::Success => 0,
-        InflateResult::InsufficientSpace => 1,
-        InflateResult::BadData => 2,
-        InflateResult::Error => 3,
+        DeflateResult::Success(size) => size as jint,
+        DeflateResult::InsufficientSpace => 0,
+        DeflateResult::Error => 0,
+    }
+}
+
+#[unsafe(no_mangle)]
+pub unsafe extern "system" fn Java_com_velocitypowered_natives_compression_NativeRawInflate_init(
+    mut env: JNIEnv,
+    _class: JClass,
+) -> jlong {
+    inflate_init(DeflateFormat::Raw) as jlong
+}
+
+#[unsafe(no_mangle)]
+pub unsafe extern "system" fn Java_com_velocitypowered_natives_compression_NativeRawInflate_free(
+    _env: JNIEnv,
+    _class: JClass,
+    ctx: jlong,
+) {
+    if ctx != 0 {
+        let _ = Box::from_raw(ctx as *mut InflateContext);
+    }
+}
+
+#[unsafe(no_mangle)]
+pub unsafe extern "system" fn Java_com_velocitypowered_natives_compression_NativeRawInflate_process(
+    mut env: JNIEnv,
+    _class: JClass,
+    ctx: jlong,
+    source_address: jlong,
+    source_length: jint,
+    destination_address: jlong,
+    destination_length: jint,
+) -> jboolean {
+    let res = inflate_process(
+        ctx as *mut InflateContext,
+        source_address as *const u8,
+        source_length as usize,
+        destination_address as *mut u8,
+        destination_length as usize,
+    );
+
+    match res {
+        InflateResult::Success(_) => JNI_TRUE,
+        InflateResult::BadData => {
+            let exception_class = env.find_class("java/util/zip/DataFormatException").unwrap();
+            env.throw_new(exception_class, "inflate data is bad")
+                .unwrap();
+            JNI_FALSE
+        }
+        InflateResult::InsufficientSpace => {
+            let exception_class = env.find_class("java/util/zip/DataFormatException").unwrap();
+            env.throw_new(exception_class, "uncompressed size is inaccurate")
+                .unwrap();
+            JNI_FALSE
+        }
+        InflateResult::Error => {
+            let exception_class = env.find_class("java/util/zip/DataFormatException").unwrap();
+            env.throw_new(exception_class, "unknown libdeflate return code")
+                .unwrap();
+            JNI_FALSE
+        }
+    }
+}
+
+#[unsafe(no_mangle)]
+pub unsafe extern "system" fn Java_com_velocitypowered_natives_compression_NativeRawInflate_processAndGetLength(
+    mut env: JNIEnv,
+    _class: JClass,
+    ctx: jlong,
+    source_address: jlong,
+    source_length: jint,
+    destination_address: jlong,
+    destination_length: jint,
+) -> jint {
+    let res = inflate_process(
+        ctx as *mut InflateContext,
+        source_address as *const u8,
+        source_length as usize,
+        destination_address as *mut u8,
+        destination_length as usize,
+    );
+
+    match res {
+        InflateResult::Success(size) => size as jint,
+        InflateResult::BadData => {
+            let exception_class = env.find_class("java/util/zip/DataFormatException").unwrap();
+            env.throw_new(exception_class, "inflate data is bad")
+                .unwrap();
+            -1
+        }
+        InflateResult::InsufficientSpace => {
+            let exception_class = env.find_class("java/util/zip/DataFormatException").unwrap();
+            env.throw_new(exception_class, "uncompressed size is inaccurate")
+                .unwrap();
+            -1
+        }
+        InflateResult::Error => {
+            let exception_class = env.find_class("java/util/zip/DataFormatException").unwrap();
+            env.throw_new(exception_class, "unknown libdeflate return code")
+                .unwrap();
+            -1
+        }
+    }
+}
+
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn rxz_deflate_init(level: c_int, format: c_int) -> *mut DeflateContext {
+    match DeflateFormat::from_c_int(format) {
+        Some(format) => deflate_init(level, format).unwrap_or_else(|| ptr::null_mut()),
+        None => ptr::null_mut(),
+    }
+}
+
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn rxz_deflate_free(ctx: *mut DeflateContext) {
+    if !ctx.is_null() {
+        let _ = Box::from_raw(ctx);
+    }
+}
+
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn rxz_deflate_process(
+    ctx: *mut DeflateContext,
+    source: *const u8,
+    source_length: c_int,
+    destination: *mut u8,
+    destination_length: c_int,
+) -> c_int {
+    let res = deflate_process(
+        ctx,
+        source,
+        source_length as usize,
+        destination,
+        destination_length as usize,
+    );
+    match res {
+        DeflateResult::Success(sz) => sz as c_int,
+        DeflateResult::InsufficientSpace => 0,
+        DeflateResult::Error => -1,
+    }
+}
+
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn rxz_deflate_bound(ctx: *mut DeflateContext, input_len: c_int) -> c_int {
+    match deflate_bound(ctx, input_len as usize) {
+        Some(bound) => bound as c_int,
+        None => -1,
+    }
+}
+
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn rxz_inflate_init(format: c_int) -> *mut InflateContext {
+    match DeflateFormat::from_c_int(format) {
+        Some(format) => inflate_init(format),
+        None => ptr::null_mut(),
+    }
+}
+
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn rxz_inflate_free(ctx: *mut InflateContext) {
+    if !ctx.is_null() {
+        let _ = Box::from_raw(ctx);
+    }
+}
+
+/// Returns the decompressed byte count on success (`>= 0`), not a plain
+/// success flag: `-1` = insufficient destination space, `-2` = bad input
+/// data, `-3` = other error. This is a breaking change from the original
+/// `{0=Success,1=InsufficientSpace,2=BadData,3=Error}` convention -- any
+/// existing C caller that treats `0` as success and a nonzero code as
+/// failure must be updated to treat `>= 0` as success instead.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn rxz_inflate_process(
+    ctx: *mut InflateContext,
+    source: *const u8,
+    source_length: c_int,
+    destination: *mut u8,
+    destination_length: c_int,
+) -> c_int {
+    let res = inflate_process(
+        ctx,
+        source,
+        source_length as usize,
+        destination,
+        destination_length as usize,
+    );
+    match res {
+        InflateResult::Success(sz) => sz as c_int,
+        InflateResult::InsufficientSpace => -1,
+        InflateResult::BadData => -2,
+        InflateResult::Error => -3,
+    }
+}
+
+struct Crc32Context {
+    crc: Crc,
+}
+
+struct Adler32Context {
+    adler: Adler32,
+}
+
+fn crc32_new() -> *mut Crc32Context {
+    Box::into_raw(Box::new(Crc32Context { crc: Crc::new() }))
+}
+
+unsafe fn crc32_update(ctx: *mut Crc32Context, ptr: *const u8, len: usize) {
+    if ctx.is_null() {
+        return;
+    }
+    let context = &mut *ctx;
+    let slice = std::slice::from_raw_parts(ptr, len);
+    context.crc.update(slice);
+}
+
+unsafe fn crc32_value(ctx: *const Crc32Context) -> u32 {
+    if ctx.is_null() {
+        return 0;
+    }
+    (*ctx).crc.sum()
+}
+
+fn adler32_new() -> *mut Adler32Context {
+    Box::into_raw(Box::new(Adler32Context {
+        adler: Adler32::new(),
+    }))
+}
+
+unsafe fn adler32_update(ctx: *mut Adler32Context, ptr: *const u8, len: usize) {
+    if ctx.is_null() {
+        return;
+    }
+    let context = &mut *ctx;
+    let slice = std::slice::from_raw_parts(ptr, len);
+    context.adler.update(slice);
+}
+
+unsafe fn adler32_value(ctx: *const Adler32Context) -> u32 {
+    if ctx.is_null() {
+        return 0;
+    }
+    (*ctx).adler.sum()
+}
+
+#[unsafe(no_mangle)]
+pub unsafe extern "system" fn Java_com_velocitypowered_natives_compression_NativeChecksum_crc32New(
+    _env: JNIEnv,
+    _class: JClass,
+) -> jlong {
+    crc32_new() as jlong
+}
+
+#[unsafe(no_mangle)]
+pub unsafe extern "system" fn Java_com_velocitypowered_natives_compression_NativeChecksum_crc32Update(
+    _env: JNIEnv,
+    _class: JClass,
+    ctx: jlong,
+    address: jlong,
+    length: jint,
+) {
+    crc32_update(
+        ctx as *mut Crc32Context,
+        address as *const u8,
+        length as usize,
+    );
+}
+
+#[unsafe(no_mangle)]
+pub unsafe extern "system" fn Java_com_velocitypowered_natives_compression_NativeChecksum_crc32Value(
+    _env: JNIEnv,
+    _class: JClass,
+    ctx: jlong,
+) -> jlong {
+    crc32_value(ctx as *const Crc32Context) as jlong
+}
+
+#[unsafe(no_mangle)]
+pub unsafe extern "system" fn Java_com_velocitypowered_natives_compression_NativeChecksum_crc32Free(
+    _env: JNIEnv,
+    _class: JClass,
+    ctx: jlong,
+) {
+    if ctx != 0 {
+        let _ = Box::from_raw(ctx as *mut Crc32Context);
+    }
+}
+
+#[unsafe(no_mangle)]
+pub unsafe extern "system" fn Java_com_velocitypowered_natives_compression_NativeChecksum_adler32New(
+    _env: JNIEnv,
+    _class: JClass,
+) -> jlong {
+    adler32_new() as jlong
+}
+
+#[unsafe(no_mangle)]
+pub unsafe extern "system" fn Java_com_velocitypowered_natives_compression_NativeChecksum_adler32Update(
+    _env: JNIEnv,
+    _class: JClass,
+    ctx: jlong,
+    address: jlong,
+    length: jint,
+) {
+    adler32_update(
+        ctx as *mut Adler32Context,
+        address as *const u8,
+        length as usize,
+    );
+}
+
+#[unsafe(no_mangle)]
+pub unsafe extern "system" fn Java_com_velocitypowered_natives_compression_NativeChecksum_adler32Value(
+    _env: JNIEnv,
+    _class: JClass,
+    ctx: jlong,
+) -> jlong {
+    adler32_value(ctx as *const Adler32Context) as jlong
+}
+
+#[unsafe(no_mangle)]
+pub unsafe extern "system" fn Java_com_velocitypowered_natives_compression_NativeChecksum_adler32Free(
+    _env: JNIEnv,
+    _class: JClass,
+    ctx: jlong,
+) {
+    if ctx != 0 {
+        let _ = Box::from_raw(ctx as *mut Adler32Context);
+    }
+}
+
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn rxz_crc32_new() -> *mut Crc32Context {
+    crc32_new()
+}
+
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn rxz_crc32_update(ctx: *mut Crc32Context, ptr: *const u8, len: c_int) {
+    crc32_update(ctx, ptr, len as usize);
+}
+
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn rxz_crc32_value(ctx: *const Crc32Context) -> u32 {
+    crc32_value(ctx)
+}
+
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn rxz_crc32_free(ctx: *mut Crc32Context) {
+    if !ctx.is_null() {
+        let _ = Box::from_raw(ctx);
+    }
+}
+
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn rxz_adler32_new() -> *mut Adler32Context {
+    adler32_new()
+}
+
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn rxz_adler32_update(ctx: *mut Adler32Context, ptr: *const u8, len: c_int) {
+    adler32_update(ctx, ptr, len as usize);
+}
+
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn rxz_adler32_value(ctx: *const Adler32Context) -> u32 {
+    adler32_value(ctx)
+}
+
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn rxz_adler32_free(ctx: *mut Adler32Context) {
+    if !ctx.is_null() {
+        let _ = Box::from_raw(ctx);
+    }
+}
+
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn rxz_zran_build_index(
+    compressed_ptr: *const u8,
+    len: c_int,
+) -> *mut ZranIndex {
+    if compressed_ptr.is_null() || len < 0 {
+        return ptr::null_mut();
+    }
+    let data = std::slice::from_raw_parts(compressed_ptr, len as usize);
+    match zran::build_index(data) {
+        Ok(index) => Box::into_raw(Box::new(index)),
+        Err(_) => ptr::null_mut(),
+    }
+}
+
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn rxz_zran_read(
+    index: *const ZranIndex,
+    compressed_ptr: *const u8,
+    compressed_len: c_int,
+    uncompressed_offset: jlong,
+    dest_ptr: *mut u8,
+    dest_len: c_int,
+) -> c_int {
+    if index.is_null() || compressed_ptr.is_null() || compressed_len < 0 || dest_len < 0 {
+        return -1;
+    }
+    let data = std::slice::from_raw_parts(compressed_ptr, compressed_len as usize);
+    let dest = std::slice::from_raw_parts_mut(dest_ptr, dest_len as usize);
+    match zran::read_at(&*index, data, uncompressed_offset as u64, dest) {
+        Ok(written) => written as c_int,
+        Err(_) => -1,
+    }
+}
+
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn rxz_zran_free(index: *mut ZranIndex) {
+    if !index.is_null() {
+        let _ = Box::from_raw(index);
+    }
+}
+
+#[repr(C)]
+pub struct RxzStreamResult {
+    pub consumed: c_int,
+    pub produced: c_int,
+    pub status: c_int,
+}
+
+const RXZ_STREAM_STATUS_ERROR: c_int = -1;
+
+fn rxz_stream_error() -> RxzStreamResult {
+    RxzStreamResult {
+        consumed: 0,
+        produced: 0,
+        status: RXZ_STREAM_STATUS_ERROR,
+    }
+}
+
+unsafe fn rxz_stream_slice<'a>(ptr: *const u8, len: c_int) -> Option<&'a [u8]> {
+    if len < 0 {
+        return None;
+    }
+    if len == 0 {
+        return Some(&[]);
+    }
+    if ptr.is_null() {
+        return None;
+    }
+    Some(std::slice::from_raw_parts(ptr, len as usize))
+}
+
+unsafe fn rxz_stream_slice_mut<'a>(ptr: *mut u8, len: c_int) -> Option<&'a mut [u8]> {
+    if len < 0 {
+        return None;
+    }
+    if len == 0 {
+        return Some(&mut []);
+    }
+    if ptr.is_null() {
+        return None;
+    }
+    Some(std::slice::from_raw_parts_mut(ptr, len as usize))
+}
+
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn rxz_deflate_stream_init(
+    level: c_int,
+    format: c_int,
+) -> *mut StreamDeflateContext {
+    stream::stream_deflate_init(level, format).unwrap_or_else(|| ptr::null_mut())
+}
+
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn rxz_deflate_stream_free(ctx: *mut StreamDeflateContext) {
+    if !ctx.is_null() {
+        let _ = Box::from_raw(ctx);
+    }
+}
+
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn rxz_deflate_stream_process(
+    ctx: *mut StreamDeflateContext,
+    input: *const u8,
+    input_len: c_int,
+    output: *mut u8,
+    output_len: c_int,
+    flush: c_int,
+) -> RxzStreamResult {
+    let (Some(in_slice), Some(out_slice)) = (
+        rxz_stream_slice(input, input_len),
+        rxz_stream_slice_mut(output, output_len),
+    ) else {
+        return rxz_stream_error();
+    };
+
+    match stream::stream_deflate_process(ctx, in_slice, out_slice, flush) {
+        Some(outcome) => RxzStreamResult {
+            consumed: outcome.consumed as c_int,
+            produced: outcome.produced as c_int,
+            status: outcome.status,
+        },
+        None => rxz_stream_error(),
+    }
+}
+
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn rxz_inflate_stream_init(format: c_int) -> *mut StreamInflateContext {
+    stream::stream_inflate_init(format).unwrap_or_else(|| ptr::null_mut())
+}
+
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn rxz_inflate_stream_free(ctx: *mut StreamInflateContext) {
+    if !ctx.is_null() {
+        let _ = Box::from_raw(ctx);
+    }
+}
+
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn rxz_inflate_stream_process(
+    ctx: *mut StreamInflateContext,
+    input: *const u8,
+    input_len: c_int,
+    output: *mut u8,
+    output_len: c_int,
+    flush: c_int,
+) -> RxzStreamResult {
+    let (Some(in_slice), Some(out_slice)) = (
+        rxz_stream_slice(input, input_len),
+        rxz_stream_slice_mut(output, output_len),
+    ) else {
+        return rxz_stream_error();
+    };
+
+    match stream::stream_inflate_process(ctx, in_slice, out_slice, flush) {
+        Some(outcome) => RxzStreamResult {
+            consumed: outcome.consumed as c_int,
+            produced: outcome.produced as c_int,
+            status: outcome.status,
+        },
+        None => rxz_stream_error(),
     }
 }
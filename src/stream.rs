@@ -0,0 +1,430 @@
+//! Chunked streaming compression/decompression for inputs that don't fit in
+//! a single preallocated buffer.
+//!
+//! libdeflater is strictly one-shot: `Compressor`/`Decompressor` need the
+//! whole message up front and a destination sized to match. This module
+//! wraps `flate2`'s `mem::Compress`/`mem::Decompress` state machines instead,
+//! which consume and produce partial chunks across repeated calls the same
+//! way zlib's own streaming `z_stream` does.
+//!
+//! Gzip (format 1) needs its own framing layer: `Compress::new_gzip`/
+//! `Decompress::new_gzip` only exist under flate2's zlib backend feature,
+//! which this crate doesn't depend on. Instead, `GzipDeflateFramer`/
+//! `GzipInflateFramer` wrap a headerless (raw DEFLATE) `Compress`/
+//! `Decompress` with a hand-rolled RFC 1952 header/trailer, using
+//! `libdeflater`'s incremental `Crc` (already a dependency) for the trailer
+//! checksum.
+
+use flate2::{Compress, Compression, Decompress, FlushCompress, FlushDecompress, Status};
+use libdeflater::Crc;
+use std::ffi::c_int;
+
+pub struct StreamDeflateContext {
+    compress: Compress,
+    gzip: Option<GzipDeflateFramer>,
+}
+
+pub struct StreamInflateContext {
+    decompress: Decompress,
+    gzip: Option<GzipInflateFramer>,
+}
+
+pub(crate) struct StreamOutcome {
+    pub consumed: usize,
+    pub produced: usize,
+    pub status: c_int,
+}
+
+/// Mirrors `DeflateFormat` in `lib.rs`: zlib/gzip/raw DEFLATE, as selected
+/// by the format argument of `rxz_deflate_stream_init`/`rxz_inflate_stream_init`.
+enum StreamFormat {
+    Zlib,
+    Gzip,
+    Raw,
+}
+
+fn stream_format_from_c_int(format: c_int) -> Option<StreamFormat> {
+    match format {
+        0 => Some(StreamFormat::Zlib),
+        1 => Some(StreamFormat::Gzip),
+        2 => Some(StreamFormat::Raw),
+        _ => None,
+    }
+}
+
+fn flush_compress_from_c_int(value: c_int) -> Option<FlushCompress> {
+    match value {
+        0 => Some(FlushCompress::None),
+        1 => Some(FlushCompress::Sync),
+        2 => Some(FlushCompress::Full),
+        3 => Some(FlushCompress::Finish),
+        _ => None,
+    }
+}
+
+fn flush_decompress_from_c_int(value: c_int) -> Option<FlushDecompress> {
+    match value {
+        0 => Some(FlushDecompress::None),
+        1 => Some(FlushDecompress::Sync),
+        2 => Some(FlushDecompress::Finish),
+        _ => None,
+    }
+}
+
+fn status_to_c_int(status: Status) -> c_int {
+    match status {
+        Status::Ok => 0,
+        Status::BufError => 1,
+        Status::StreamEnd => 2,
+    }
+}
+
+/// Fixed 10-byte gzip header with no optional fields: magic `1f 8b`, deflate
+/// method `08`, no flags, zero mtime, no level hint, and the "unknown" OS
+/// byte -- the same minimal header flate2's own gzip encoder emits for a
+/// stream with no filename/extra-field/comment.
+const GZIP_HEADER: [u8; 10] = [0x1f, 0x8b, 0x08, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0xff];
+
+#[derive(Clone, Copy)]
+enum GzipDeflatePhase {
+    Header(usize),
+    Body,
+    Trailer { bytes: [u8; 8], pos: usize },
+    Done,
+}
+
+/// Wraps a raw (headerless) `Compress` with the RFC 1952 gzip framing that
+/// `Compress::new_gzip` would otherwise provide.
+struct GzipDeflateFramer {
+    crc: Crc,
+    total_in: u32,
+    phase: GzipDeflatePhase,
+}
+
+impl GzipDeflateFramer {
+    fn new() -> Self {
+        Self {
+            crc: Crc::new(),
+            total_in: 0,
+            phase: GzipDeflatePhase::Header(0),
+        }
+    }
+
+    fn process(
+        &mut self,
+        compress: &mut Compress,
+        input: &[u8],
+        output: &mut [u8],
+        flush: FlushCompress,
+    ) -> Option<StreamOutcome> {
+        if matches!(self.phase, GzipDeflatePhase::Done) {
+            return Some(StreamOutcome {
+                consumed: 0,
+                produced: 0,
+                status: status_to_c_int(Status::StreamEnd),
+            });
+        }
+
+        let mut produced = 0usize;
+
+        if let GzipDeflatePhase::Header(pos) = self.phase {
+            let remaining = &GZIP_HEADER[pos..];
+            let take = remaining.len().min(output.len());
+            output[..take].copy_from_slice(&remaining[..take]);
+            produced += take;
+            let new_pos = pos + take;
+            if new_pos == GZIP_HEADER.len() {
+                self.phase = GzipDeflatePhase::Body;
+            } else {
+                self.phase = GzipDeflatePhase::Header(new_pos);
+                return Some(StreamOutcome {
+                    consumed: 0,
+                    produced,
+                    status: status_to_c_int(Status::Ok),
+                });
+            }
+        }
+
+        let consumed = if matches!(self.phase, GzipDeflatePhase::Body) {
+            let before_in = compress.total_in();
+            let before_out = compress.total_out();
+            let status = compress.compress(input, &mut output[produced..], flush).ok()?;
+            let consumed_now = (compress.total_in() - before_in) as usize;
+            let produced_now = (compress.total_out() - before_out) as usize;
+            self.crc.update(&input[..consumed_now]);
+            self.total_in = self.total_in.wrapping_add(consumed_now as u32);
+            produced += produced_now;
+
+            if status != Status::StreamEnd {
+                return Some(StreamOutcome {
+                    consumed: consumed_now,
+                    produced,
+                    status: status_to_c_int(status),
+                });
+            }
+
+            let mut bytes = [0u8; 8];
+            bytes[..4].copy_from_slice(&self.crc.sum().to_le_bytes());
+            bytes[4..].copy_from_slice(&self.total_in.to_le_bytes());
+            self.phase = GzipDeflatePhase::Trailer { bytes, pos: 0 };
+            consumed_now
+        } else {
+            0
+        };
+
+        if let GzipDeflatePhase::Trailer { bytes, pos } = self.phase {
+            let remaining = &bytes[pos..];
+            let take = remaining.len().min(output.len() - produced);
+            output[produced..produced + take].copy_from_slice(&remaining[..take]);
+            produced += take;
+            let new_pos = pos + take;
+            let status = if new_pos == bytes.len() {
+                self.phase = GzipDeflatePhase::Done;
+                Status::StreamEnd
+            } else {
+                self.phase = GzipDeflatePhase::Trailer { bytes, pos: new_pos };
+                Status::Ok
+            };
+            return Some(StreamOutcome {
+                consumed,
+                produced,
+                status: status_to_c_int(status),
+            });
+        }
+
+        Some(StreamOutcome {
+            consumed,
+            produced,
+            status: status_to_c_int(Status::Ok),
+        })
+    }
+}
+
+/// Returns `Some(None)` if the bytes buffered so far are an incomplete gzip
+/// header, `Some(Some(len))` once the header's exact length (including any
+/// FEXTRA/FNAME/FCOMMENT/FHCRC fields) is known, or `None` if they don't
+/// start with a valid gzip magic/method.
+fn gzip_header_len(buf: &[u8]) -> Option<Option<usize>> {
+    if buf.len() < 10 {
+        return Some(None);
+    }
+    if buf[0] != 0x1f || buf[1] != 0x8b || buf[2] != 0x08 {
+        return None;
+    }
+    let flg = buf[3];
+    let mut pos = 10usize;
+
+    if flg & 0x04 != 0 {
+        if buf.len() < pos + 2 {
+            return Some(None);
+        }
+        let xlen = u16::from_le_bytes([buf[pos], buf[pos + 1]]) as usize;
+        pos += 2 + xlen;
+        if buf.len() < pos {
+            return Some(None);
+        }
+    }
+    if flg & 0x08 != 0 {
+        match buf[pos..].iter().position(|&b| b == 0) {
+            Some(n) => pos += n + 1,
+            None => return Some(None),
+        }
+    }
+    if flg & 0x10 != 0 {
+        match buf[pos..].iter().position(|&b| b == 0) {
+            Some(n) => pos += n + 1,
+            None => return Some(None),
+        }
+    }
+    if flg & 0x02 != 0 {
+        pos += 2;
+        if buf.len() < pos {
+            return Some(None);
+        }
+    }
+    Some(Some(pos))
+}
+
+/// Wraps a raw (headerless) `Decompress` with RFC 1952 gzip header parsing,
+/// the counterpart to `GzipDeflateFramer`. The 8-byte trailer (CRC32 +
+/// ISIZE) is not validated. Trailer bytes passed in after the payload hits
+/// `Status::StreamEnd` are left unconsumed; trailer bytes that arrived in
+/// the same input as the header are reported as consumed and dropped.
+struct GzipInflateFramer {
+    header_done: bool,
+    header_buf: Vec<u8>,
+    body_prefix: Vec<u8>,
+    done: bool,
+}
+
+impl GzipInflateFramer {
+    fn new() -> Self {
+        Self {
+            header_done: false,
+            header_buf: Vec::new(),
+            body_prefix: Vec::new(),
+            done: false,
+        }
+    }
+
+    fn decompress_prefix(
+        &mut self,
+        decompress: &mut Decompress,
+        output: &mut [u8],
+        flush: FlushDecompress,
+    ) -> Option<(usize, Status)> {
+        let before_in = decompress.total_in();
+        let before_out = decompress.total_out();
+        let status = decompress
+            .decompress(&self.body_prefix, output, flush)
+            .ok()?;
+        let used = (decompress.total_in() - before_in) as usize;
+        self.body_prefix.drain(..used);
+        let produced = (decompress.total_out() - before_out) as usize;
+        if status == Status::StreamEnd {
+            self.done = true;
+        }
+        Some((produced, status))
+    }
+
+    fn process(
+        &mut self,
+        decompress: &mut Decompress,
+        input: &[u8],
+        output: &mut [u8],
+        flush: FlushDecompress,
+    ) -> Option<StreamOutcome> {
+        if self.done {
+            return Some(StreamOutcome {
+                consumed: 0,
+                produced: 0,
+                status: status_to_c_int(Status::StreamEnd),
+            });
+        }
+
+        if !self.header_done {
+            self.header_buf.extend_from_slice(input);
+            let header_len = match gzip_header_len(&self.header_buf)? {
+                None => {
+                    return Some(StreamOutcome {
+                        consumed: input.len(),
+                        produced: 0,
+                        status: status_to_c_int(Status::Ok),
+                    });
+                }
+                Some(len) => len,
+            };
+            self.body_prefix = self.header_buf.split_off(header_len);
+            self.header_buf.clear();
+            self.header_done = true;
+
+            let (produced, status) = self.decompress_prefix(decompress, output, flush)?;
+            return Some(StreamOutcome {
+                consumed: input.len(),
+                produced,
+                status: status_to_c_int(status),
+            });
+        }
+
+        if !self.body_prefix.is_empty() {
+            let (produced, status) = self.decompress_prefix(decompress, output, flush)?;
+            return Some(StreamOutcome {
+                consumed: 0,
+                produced,
+                status: status_to_c_int(status),
+            });
+        }
+
+        let before_in = decompress.total_in();
+        let before_out = decompress.total_out();
+        let status = decompress.decompress(input, output, flush).ok()?;
+        let consumed = (decompress.total_in() - before_in) as usize;
+        let produced = (decompress.total_out() - before_out) as usize;
+        if status == Status::StreamEnd {
+            self.done = true;
+        }
+        Some(StreamOutcome {
+            consumed,
+            produced,
+            status: status_to_c_int(status),
+        })
+    }
+}
+
+pub(crate) fn stream_deflate_init(level: c_int, format: c_int) -> Option<*mut StreamDeflateContext> {
+    let format = stream_format_from_c_int(format)?;
+    if !(0..=9).contains(&level) {
+        return None;
+    }
+    let level = Compression::new(level as u32);
+    let (compress, gzip) = match format {
+        StreamFormat::Zlib => (Compress::new(level, true), None),
+        StreamFormat::Raw => (Compress::new(level, false), None),
+        StreamFormat::Gzip => (Compress::new(level, false), Some(GzipDeflateFramer::new())),
+    };
+    Some(Box::into_raw(Box::new(StreamDeflateContext { compress, gzip })))
+}
+
+pub(crate) fn stream_inflate_init(format: c_int) -> Option<*mut StreamInflateContext> {
+    let format = stream_format_from_c_int(format)?;
+    let (decompress, gzip) = match format {
+        StreamFormat::Zlib => (Decompress::new(true), None),
+        StreamFormat::Raw => (Decompress::new(false), None),
+        StreamFormat::Gzip => (Decompress::new(false), Some(GzipInflateFramer::new())),
+    };
+    Some(Box::into_raw(Box::new(StreamInflateContext { decompress, gzip })))
+}
+
+pub(crate) unsafe fn stream_deflate_process(
+    ctx: *mut StreamDeflateContext,
+    input: &[u8],
+    output: &mut [u8],
+    flush: c_int,
+) -> Option<StreamOutcome> {
+    if ctx.is_null() {
+        return None;
+    }
+    let flush = flush_compress_from_c_int(flush)?;
+    let context = &mut *ctx;
+
+    if let Some(gzip) = context.gzip.as_mut() {
+        return gzip.process(&mut context.compress, input, output, flush);
+    }
+
+    let before_in = context.compress.total_in();
+    let before_out = context.compress.total_out();
+    let status = context.compress.compress(input, output, flush).ok()?;
+
+    Some(StreamOutcome {
+        consumed: (context.compress.total_in() - before_in) as usize,
+        produced: (context.compress.total_out() - before_out) as usize,
+        status: status_to_c_int(status),
+    })
+}
+
+pub(crate) unsafe fn stream_inflate_process(
+    ctx: *mut StreamInflateContext,
+    input: &[u8],
+    output: &mut [u8],
+    flush: c_int,
+) -> Option<StreamOutcome> {
+    if ctx.is_null() {
+        return None;
+    }
+    let flush = flush_decompress_from_c_int(flush)?;
+    let context = &mut *ctx;
+
+    if let Some(gzip) = context.gzip.as_mut() {
+        return gzip.process(&mut context.decompress, input, output, flush);
+    }
+
+    let before_in = context.decompress.total_in();
+    let before_out = context.decompress.total_out();
+    let status = context.decompress.decompress(input, output, flush).ok()?;
+
+    Some(StreamOutcome {
+        consumed: (context.decompress.total_in() - before_in) as usize,
+        produced: (context.decompress.total_out() - before_out) as usize,
+        status: status_to_c_int(status),
+    })
+}
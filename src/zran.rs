@@ -0,0 +1,304 @@
+//! Indexed random-access decompression over a zlib/gzip stream.
+//!
+//! libdeflater's compressors/decompressors are strictly one-shot, so random
+//! access into a large compressed blob is built on top of `libz-sys`
+//! instead: the stream is inflated once (its zlib or gzip header detected
+//! automatically) with `Z_BLOCK` flushes so `inflate` hands control back at
+//! every DEFLATE block boundary, and a checkpoint is recorded every `SPAN`
+//! bytes of compressed input. Reads resume decoding in raw mode from the
+//! nearest checkpoint by priming the leftover bits
+//! and seeding the sliding-window dictionary libz needs to keep producing
+//! identical output from that point on.
+
+use libz_sys::{
+    inflate, inflateEnd, inflateInit2_, inflatePrime, inflateSetDictionary, uInt, z_stream,
+    zlibVersion, Z_BLOCK, Z_NO_FLUSH, Z_OK, Z_STREAM_END,
+};
+use std::collections::VecDeque;
+use std::ffi::c_int;
+use std::mem::{self, MaybeUninit};
+use std::os::raw::c_void;
+use std::ptr::addr_of_mut;
+
+// `z_stream::zalloc`/`zfree` are non-nullable `extern "C" fn` pointers, not
+// `Option<extern "C" fn>`, so zero-initializing a whole `z_stream` (as
+// `mem::zeroed()` would) manufactures an invalid (null) function pointer --
+// undefined behavior that aborts the process as soon as it's touched. zlib
+// itself only falls back to its built-in allocator when `zalloc`/`zfree` are
+// literally null at the C level, a state Rust's type system can't represent
+// here, so we supply real (malloc/free-backed) allocator callbacks instead.
+unsafe extern "C" {
+    fn malloc(size: usize) -> *mut c_void;
+    fn free(ptr: *mut c_void);
+}
+
+unsafe extern "C" fn zran_zalloc(_opaque: *mut c_void, items: uInt, size: uInt) -> *mut c_void {
+    malloc(items as usize * size as usize)
+}
+
+unsafe extern "C" fn zran_zfree(_opaque: *mut c_void, address: *mut c_void) {
+    free(address)
+}
+
+const WINDOW_SIZE: usize = 32 * 1024;
+const SPAN: u64 = 1024 * 1024;
+const CHUNK: usize = 64 * 1024;
+
+/// Auto-detect a zlib or gzip header (RFC 1950 / RFC 1952).
+const HEADER_AUTODETECT_BITS: c_int = 15 + 32;
+/// Raw, headerless DEFLATE, used when resuming mid-stream from a checkpoint.
+const RAW_BITS: c_int = -15;
+
+#[derive(Debug)]
+pub(crate) enum ZranError {
+    Init,
+    Data,
+}
+
+struct ZranCheckpoint {
+    /// Byte offset into the compressed stream at which this checkpoint resumes.
+    compressed_byte_offset: u64,
+    /// Offset into the uncompressed output this checkpoint resumes at.
+    uncompressed_offset: u64,
+    /// Up to the last 32 KiB of output produced so far, used to seed the
+    /// decoder's sliding-window dictionary on resume.
+    window: Box<[u8; WINDOW_SIZE]>,
+    window_len: usize,
+    /// Unused bits (0-7) left over in the byte at `compressed_byte_offset - 1`.
+    bits: u8,
+}
+
+pub struct ZranIndex {
+    checkpoints: Vec<ZranCheckpoint>,
+}
+
+unsafe fn new_inflate_stream(window_bits: c_int) -> Result<z_stream, ()> {
+    // Zero the raw bytes (valid for every field except zalloc/zfree, which we
+    // overwrite below before the value is ever observed as a `z_stream`) and
+    // only then assume_init, so no invalid function pointer is ever
+    // materialized -- unlike `mem::zeroed::<z_stream>()`.
+    let mut strm = MaybeUninit::<z_stream>::zeroed();
+    let ptr = strm.as_mut_ptr();
+    addr_of_mut!((*ptr).zalloc).write(zran_zalloc);
+    addr_of_mut!((*ptr).zfree).write(zran_zfree);
+    let mut strm = strm.assume_init();
+
+    let ret = inflateInit2_(
+        &mut strm,
+        window_bits,
+        zlibVersion(),
+        mem::size_of::<z_stream>() as c_int,
+    );
+    if ret == Z_OK { Ok(strm) } else { Err(()) }
+}
+
+pub(crate) fn build_index(data: &[u8]) -> Result<ZranIndex, ZranError> {
+    unsafe {
+        let mut strm = new_inflate_stream(HEADER_AUTODETECT_BITS).map_err(|_| ZranError::Init)?;
+        strm.next_in = data.as_ptr() as *mut u8;
+        strm.avail_in = data.len() as u32;
+
+        let mut checkpoints = Vec::new();
+        // Rolling last-32-KiB window of output, used to seed the dictionary
+        // on resume; indexing a multi-GiB blob must not require holding the
+        // whole decompressed stream in memory just to take this slice.
+        let mut window_buf: VecDeque<u8> = VecDeque::with_capacity(WINDOW_SIZE);
+        let mut last_checkpoint_in: u64 = 0;
+        let mut total_in: u64 = 0;
+        let mut total_out: u64 = 0;
+        let mut out_buf = vec![0u8; WINDOW_SIZE];
+
+        let result = loop {
+            strm.next_out = out_buf.as_mut_ptr();
+            strm.avail_out = out_buf.len() as u32;
+
+            let before_in = strm.avail_in;
+            let before_out = strm.avail_out;
+            let ret = inflate(&mut strm, Z_BLOCK);
+            total_in += (before_in - strm.avail_in) as u64;
+            let produced_now = (before_out - strm.avail_out) as usize;
+            total_out += produced_now as u64;
+
+            for &byte in &out_buf[..produced_now] {
+                if window_buf.len() == WINDOW_SIZE {
+                    window_buf.pop_front();
+                }
+                window_buf.push_back(byte);
+            }
+
+            if ret == Z_STREAM_END {
+                break Ok(());
+            }
+            if ret != Z_OK {
+                break Err(ZranError::Data);
+            }
+
+            // data_type & 0xc0 == 0x80 means inflate stopped exactly at a
+            // block boundary (or just after the stream header) and the block
+            // just finished was not the last one, so total_in/total_out are
+            // valid resume points.
+            if strm.data_type & 0xc0 == 0x80
+                && (checkpoints.is_empty() || total_in - last_checkpoint_in > SPAN)
+            {
+                let window_len = window_buf.len();
+                let mut window = Box::new([0u8; WINDOW_SIZE]);
+                let (head, tail) = window_buf.as_slices();
+                window[..head.len()].copy_from_slice(head);
+                window[head.len()..head.len() + tail.len()].copy_from_slice(tail);
+                checkpoints.push(ZranCheckpoint {
+                    compressed_byte_offset: total_in,
+                    uncompressed_offset: total_out,
+                    window,
+                    window_len,
+                    bits: (strm.data_type & 0x07) as u8,
+                });
+                last_checkpoint_in = total_in;
+            }
+
+            if strm.avail_in == 0 && produced_now == 0 {
+                break Err(ZranError::Data);
+            }
+        };
+
+        inflateEnd(&mut strm);
+        result?;
+        Ok(ZranIndex { checkpoints })
+    }
+}
+
+pub(crate) fn read_at(
+    index: &ZranIndex,
+    data: &[u8],
+    uncompressed_offset: u64,
+    dest: &mut [u8],
+) -> Result<usize, ZranError> {
+    let checkpoint_pos = index
+        .checkpoints
+        .partition_point(|cp| cp.uncompressed_offset <= uncompressed_offset);
+    let checkpoint = if checkpoint_pos == 0 {
+        None
+    } else {
+        Some(&index.checkpoints[checkpoint_pos - 1])
+    };
+
+    let (window_bits, input_pos, discard) = match checkpoint {
+        Some(cp) => (
+            RAW_BITS,
+            cp.compressed_byte_offset as usize,
+            (uncompressed_offset - cp.uncompressed_offset) as usize,
+        ),
+        // No checkpoint covers this offset yet (it falls within the first
+        // block of output), so decode from the real start of the stream,
+        // header and all, with an empty dictionary.
+        None => (HEADER_AUTODETECT_BITS, 0usize, uncompressed_offset as usize),
+    };
+
+    unsafe {
+        let mut strm = new_inflate_stream(window_bits).map_err(|_| ZranError::Init)?;
+
+        if let Some(cp) = checkpoint {
+            if cp.bits != 0 {
+                // The leftover bits belong to the last byte consumed before
+                // this checkpoint, i.e. the byte just before input_pos --
+                // decoding itself still resumes at input_pos.
+                let last_consumed_byte = input_pos
+                    .checked_sub(1)
+                    .and_then(|i| data.get(i))
+                    .copied()
+                    .ok_or(ZranError::Data)?;
+                inflatePrime(
+                    &mut strm,
+                    cp.bits as c_int,
+                    (last_consumed_byte >> (8 - cp.bits)) as c_int,
+                );
+            }
+            if cp.window_len > 0
+                && inflateSetDictionary(&mut strm, cp.window.as_ptr(), cp.window_len as u32)
+                    != Z_OK
+            {
+                inflateEnd(&mut strm);
+                return Err(ZranError::Data);
+            }
+        }
+
+        let remaining = data.get(input_pos..).ok_or(ZranError::Data)?;
+        strm.next_in = remaining.as_ptr() as *mut u8;
+        strm.avail_in = remaining.len() as u32;
+
+        let mut scratch = [0u8; CHUNK];
+        let mut discard = discard;
+        let mut written = 0usize;
+
+        let result = loop {
+            if written >= dest.len() {
+                break Ok(written);
+            }
+
+            strm.next_out = scratch.as_mut_ptr();
+            strm.avail_out = CHUNK as u32;
+            let ret = inflate(&mut strm, Z_NO_FLUSH);
+            let produced = CHUNK - strm.avail_out as usize;
+            let mut produced_slice = &scratch[..produced];
+
+            if discard > 0 {
+                let skip_now = discard.min(produced_slice.len());
+                produced_slice = &produced_slice[skip_now..];
+                discard -= skip_now;
+            }
+            let take = produced_slice.len().min(dest.len() - written);
+            dest[written..written + take].copy_from_slice(&produced_slice[..take]);
+            written += take;
+
+            if ret == Z_STREAM_END {
+                break Ok(written);
+            }
+            if ret != Z_OK {
+                break Err(ZranError::Data);
+            }
+            if strm.avail_in == 0 && produced == 0 {
+                break Ok(written);
+            }
+        };
+
+        inflateEnd(&mut strm);
+        result
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use libdeflater::{CompressionLvl, Compressor};
+
+    /// Compresses a multi-MiB buffer of pseudo-random bytes (so the compressed
+    /// stream is itself several MiB and `build_index` lays down several
+    /// `SPAN`-spaced checkpoints, not just the initial one), then reads back
+    /// chunks at offsets that land past the first checkpoint and checks the
+    /// decoded bytes match the original input exactly.
+    #[test]
+    fn round_trip_past_first_checkpoint() {
+        let mut original = Vec::with_capacity(4 * SPAN as usize);
+        let mut state: u32 = 0x9e37_79b9;
+        for _ in 0..original.capacity() {
+            state ^= state << 13;
+            state ^= state >> 17;
+            state ^= state << 5;
+            original.push(state as u8);
+        }
+
+        let mut compressor = Compressor::new(CompressionLvl::default());
+        let mut compressed = vec![0u8; compressor.zlib_compress_bound(original.len())];
+        let compressed_len = compressor
+            .zlib_compress(&original, &mut compressed)
+            .expect("compress");
+        compressed.truncate(compressed_len);
+
+        let index = build_index(&compressed).expect("build_index");
+
+        for &offset in &[0u64, SPAN, 2 * SPAN + 17, 3 * SPAN + 12_345] {
+            let mut dest = vec![0u8; 4096];
+            let written = read_at(&index, &compressed, offset, &mut dest).expect("read_at");
+            assert_eq!(&dest[..written], &original[offset as usize..offset as usize + written]);
+        }
+    }
+}